@@ -1,91 +1,201 @@
-use crossterm::event::{self, Event, KeyCode};
+use crossterm::event::{Event as CrosstermEvent, KeyCode};
 use std::{
     collections::HashMap,
-    io::{self, BufRead, BufReader},
+    io::{self, BufRead, BufReader, Seek, SeekFrom},
     fs::File,
-    error::Error
+    error::Error,
+    sync::mpsc::Receiver,
 };
 use tui::{
     backend::Backend,
     layout::Rect,
     Terminal
 };
-use crate::view::draw_ui;
+use crate::command;
+use crate::config::Config;
+use crate::parser::{self, LogEntry};
+use crate::prompt::{self, Prompt, PromptOutcome};
+use crate::view::{draw_ui, CHROME_HEIGHT};
+use crate::Event;
+
+/// Which way a pending management action moves a user relative to the `sudo` group.
+#[derive(Clone, Copy)]
+pub enum ManageAction {
+    Add,
+    Remove,
+}
+
+/// The MANAGE tab's state machine: browsing the sudoers list, or mid-flow on an
+/// add/remove action that still needs a username, a y/n confirmation, or a password.
+pub enum ManageStep {
+    Idle,
+    EnteringUser,
+    ConfirmAction { action: ManageAction, user: String },
+    EnteringPassword { action: ManageAction, user: String },
+    Result(String),
+}
 
 pub struct App {
-    pub logs: Vec<String>,
+    /// Log source path and color theme, loaded once at startup from
+    /// `~/.config/tuisulog/config.toml` (or built-in defaults if absent).
+    pub config: Config,
+    pub logs: Vec<LogEntry>,
     pub num_logs: usize,
-    pub sudo_logs: Vec<String>,
+    pub sudo_logs: Vec<LogEntry>,
     pub commands: HashMap<String, usize>,
     pub titles: Vec<String>,
     pub tab_index: usize,
-    pub page_index: usize,
-    pub num_pages: usize,
+    /// Index of the first visible log entry in the current tab's filtered list.
+    pub scroll: usize,
+    /// How many log rows are visible at once, derived from the terminal height.
     pub logs_per_page: usize,
+    /// When true, the view stays pinned to the newest page as logs are tailed in.
+    pub follow: bool,
+    /// Byte offset into the log file that has already been read, so ticks only
+    /// need to parse newly-appended bytes instead of rereading the whole file.
+    offset: u64,
+    /// The search/filter prompt opened with '/'.
+    pub prompt: Prompt,
+    /// Whether the prompt is currently focused and consuming key input.
+    pub searching: bool,
+    /// Indices into `logs` (tab 0) or `sudo_logs` (tabs 1/2) that match the current
+    /// filter query, recomputed each frame in `update_log_information`.
+    pub filtered: Vec<usize>,
+    /// Current members of the `sudo` group, shown on the MANAGE tab.
+    pub sudoers: Vec<String>,
+    /// Index of the highlighted row in the MANAGE tab's sudoers list.
+    pub manage_selected: usize,
+    /// The MANAGE tab's current state (browsing, or mid add/remove flow).
+    pub manage_step: ManageStep,
+    /// Text input reused by the MANAGE tab for usernames and (masked) passwords.
+    pub manage_input: Prompt,
 }
 
 impl App {
-    pub fn new() -> App {
+    /// Builds the initial `App` state by loading the config and reading the
+    /// configured log file. Returns an error (rather than panicking) if the
+    /// log path can't be opened, so the caller can report it before the
+    /// terminal is put into raw/alternate-screen mode.
+    pub fn new() -> Result<App, Box<dyn Error>> {
+        let config = Config::load();
         let mut commands = HashMap::new();
-        let (logs, sudo_logs) = load_logs(&mut commands).unwrap();
-        App {
+        let (logs, sudo_logs, offset) = load_logs(&config.log_path, &mut commands)?;
+        Ok(App {
+            config,
             logs,
             num_logs: 0,
             sudo_logs,
             commands,
-            titles: vec!["ALL".to_string(), "SUDO".to_string(), "COMMANDS".to_string()],
+            titles: vec!["ALL".to_string(), "SUDO".to_string(), "COMMANDS".to_string(), "MANAGE".to_string()],
             tab_index: 0,
-            page_index: 0,
-            num_pages: 0,
-            logs_per_page: 0
-        }
+            // pinned to the bottom (newest logs) until the first draw clamps it
+            scroll: usize::MAX,
+            logs_per_page: 0,
+            follow: true,
+            offset,
+            prompt: Prompt::new(),
+            searching: false,
+            filtered: Vec::new(),
+            sudoers: get_sudoers().unwrap_or_default(),
+            manage_selected: 0,
+            manage_step: ManageStep::Idle,
+            manage_input: Prompt::new(),
+        })
     }
 
-    pub fn run<B: Backend>(mut self, terminal: &mut Terminal<B>) -> io::Result<()> {
-        let mut set_start_page = true;
-
+    pub fn run<B: Backend>(mut self, terminal: &mut Terminal<B>, rx: Receiver<Event>) -> io::Result<()> {
         loop {
             terminal.draw(|f| {
                 let size = f.size();
                 /* calculates necessary information, including:
-                 * logs_per_page: the number of logs than can be displayed per page
-                 * num_logs:      the total number of logs
-                 * num_pages:     the total number of pages 
+                 * logs_per_page: the number of log rows that fit in the viewport
+                 * num_logs:      the total number of (filtered) logs
                  */
                 update_log_information(&mut self, &size);
 
-                if set_start_page {
-                    self.page_index = self.num_pages - 1;
-                    set_start_page = false;
-                }
+                // pin to the bottom once the filtered set or viewport shrinks
+                let max_scroll = self.num_logs.saturating_sub(self.logs_per_page);
+                self.scroll = self.scroll.min(max_scroll);
 
                 // draws the ui
                 draw_ui(f, &self, &size);
             })?;
 
-            // handles all key inputs
-            if let Event::Key(key) = event::read()? {
-                match key.code {
-                    KeyCode::Char('q') => return Ok(()),
-                    KeyCode::Up => {
-                        if self.page_index != 0 {
-                            self.page_index -= 1;
+            // handles both key input and the periodic tick used to tail the log file
+            match rx.recv().map_err(|e| io::Error::new(io::ErrorKind::Other, e))? {
+                Event::Input(CrosstermEvent::Key(key)) if self.searching => {
+                    match self.prompt.handle_key(key.code) {
+                        PromptOutcome::Submit => self.searching = false,
+                        PromptOutcome::Cancel => {
+                            self.prompt.clear();
+                            self.searching = false;
                         }
+                        PromptOutcome::Continue => {}
                     }
-                    KeyCode::Down => {
-                        if self.page_index != self.num_pages - 1 {
-                            self.page_index += 1;
+                    // the match set changes on every keystroke, so re-anchor to the top
+                    self.scroll = 0;
+                }
+                Event::Input(CrosstermEvent::Key(key)) if self.tab_index == 3 && !matches!(self.manage_step, ManageStep::Idle) => {
+                    self.handle_manage_input(key.code);
+                }
+                Event::Input(CrosstermEvent::Key(key)) => {
+                    match key.code {
+                        KeyCode::Char('q') => return Ok(()),
+                        KeyCode::Char('f') => self.follow = !self.follow,
+                        KeyCode::Char('/') => self.searching = true,
+                        KeyCode::Char('a') if self.tab_index == 3 => {
+                            self.manage_input.clear();
+                            self.manage_step = ManageStep::EnteringUser;
+                        }
+                        KeyCode::Enter if self.tab_index == 3 => {
+                            if let Some(user) = self.sudoers.get(self.manage_selected).cloned() {
+                                self.manage_step = ManageStep::ConfirmAction { action: ManageAction::Remove, user };
+                            }
+                        }
+                        KeyCode::Up if self.tab_index == 3 => {
+                            self.manage_selected = self.manage_selected.saturating_sub(1);
+                        }
+                        KeyCode::Down if self.tab_index == 3 => {
+                            if self.manage_selected + 1 < self.sudoers.len() {
+                                self.manage_selected += 1;
+                            }
+                        }
+                        KeyCode::Up => {
+                            self.scroll = self.scroll.saturating_sub(1);
+                        }
+                        KeyCode::Down => {
+                            self.scroll = self.scroll.saturating_add(1);
+                        }
+                        KeyCode::PageUp => {
+                            self.scroll = self.scroll.saturating_sub(self.logs_per_page);
+                        }
+                        KeyCode::PageDown => {
+                            self.scroll = self.scroll.saturating_add(self.logs_per_page);
+                        }
+                        KeyCode::Home => {
+                            self.scroll = 0;
                         }
+                        KeyCode::End => {
+                            self.scroll = usize::MAX;
+                        }
+                        KeyCode::Right => {
+                            self.next();
+                            self.scroll = usize::MAX;
+                        },
+                        KeyCode::Left => {
+                            self.prev();
+                            self.scroll = usize::MAX;
+                        },
+                        _ => {}
+                    }
+                }
+                Event::Input(_) => {}
+                Event::Tick => {
+                    let max_scroll = self.num_logs.saturating_sub(self.logs_per_page);
+                    let was_pinned = self.scroll >= max_scroll;
+                    if self.tail_logs().unwrap_or(false) && self.follow && was_pinned {
+                        self.scroll = usize::MAX;
                     }
-                    KeyCode::Right => {
-                        self.next();
-                        set_start_page = true;
-                    },
-                    KeyCode::Left => {
-                        self.prev();
-                        set_start_page = true;
-                    },
-                    _ => {}
                 }
             }
         }
@@ -102,11 +212,126 @@ impl App {
             self.tab_index = self.titles.len() - 1;
         }
     }
+
+    /// Reads any bytes appended to the log file since the last read, parses the new lines
+    /// the same way `load_logs` does, and folds them into `logs`, `sudo_logs`, and `commands`.
+    /// Returns whether any new lines were read.
+    fn tail_logs(&mut self) -> Result<bool, Box<dyn Error>> {
+        let mut file = File::open(&self.config.log_path)?;
+        let len = file.metadata()?.len();
+        if len <= self.offset {
+            return Ok(false);
+        }
+
+        file.seek(SeekFrom::Start(self.offset))?;
+        let reader = BufReader::new(file);
+        let mut read_any = false;
+        for line in reader.lines() {
+            let line = line?;
+            process_line(&line, &mut self.logs, &mut self.sudo_logs, &mut self.commands);
+            read_any = true;
+        }
+        self.offset = len;
+
+        Ok(read_any)
+    }
+
+    /// Advances the MANAGE tab's state machine while it's mid add/remove flow.
+    fn handle_manage_input(&mut self, code: KeyCode) {
+        match &self.manage_step {
+            ManageStep::EnteringUser => {
+                match self.manage_input.handle_key(code) {
+                    PromptOutcome::Submit => {
+                        let user = self.manage_input.query().to_string();
+                        self.manage_input.clear();
+                        self.manage_step = ManageStep::ConfirmAction { action: ManageAction::Add, user };
+                    }
+                    PromptOutcome::Cancel => {
+                        self.manage_input.clear();
+                        self.manage_step = ManageStep::Idle;
+                    }
+                    PromptOutcome::Continue => {}
+                }
+            }
+            ManageStep::ConfirmAction { action, user } => {
+                let action = *action;
+                let user = user.clone();
+                match code {
+                    KeyCode::Char('y') => {
+                        self.manage_input.clear();
+                        self.manage_step = ManageStep::EnteringPassword { action, user };
+                    }
+                    KeyCode::Char('n') | KeyCode::Esc => self.manage_step = ManageStep::Idle,
+                    _ => {}
+                }
+            }
+            ManageStep::EnteringPassword { action, user } => {
+                let action = *action;
+                let user = user.clone();
+                match self.manage_input.handle_key(code) {
+                    PromptOutcome::Submit => {
+                        let password = self.manage_input.query().to_string();
+                        self.manage_input.clear();
+                        self.run_manage_command(action, &user, &password);
+                    }
+                    PromptOutcome::Cancel => {
+                        self.manage_input.clear();
+                        self.manage_step = ManageStep::Idle;
+                    }
+                    PromptOutcome::Continue => {}
+                }
+            }
+            ManageStep::Result(_) => {
+                // any key dismisses the result message
+                self.manage_step = ManageStep::Idle;
+            }
+            ManageStep::Idle => {}
+        }
+    }
+
+    /// Runs the privileged add/remove command and, on success, re-reads `/etc/group` so
+    /// the sudoers list reflects the change. Failures are surfaced as a message rather
+    /// than panicking.
+    fn run_manage_command(&mut self, action: ManageAction, user: &str, password: &str) {
+        let result = match action {
+            ManageAction::Add => command::add_to_sudo(user, password),
+            ManageAction::Remove => command::remove_from_sudo(user, password),
+        };
+
+        self.manage_step = match result {
+            Ok(output) if output.success => {
+                self.sudoers = get_sudoers().unwrap_or_default();
+                self.manage_selected = 0;
+                let verb = match action { ManageAction::Add => "added", ManageAction::Remove => "removed" };
+                ManageStep::Result(format!("{} {} {} sudo", user, verb, match action { ManageAction::Add => "to", ManageAction::Remove => "from" }))
+            }
+            Ok(output) => ManageStep::Result(format!("command failed: {}", output.output.trim())),
+            Err(err) => ManageStep::Result(format!("command failed: {}", err)),
+        };
+    }
 }
 
-fn load_logs(commands: &mut HashMap<String, usize>) -> Result<(Vec<String>, Vec<String>), Box<dyn Error>> {
+/// Parses a single auth.log line, recording it in `logs`, in `sudo_logs` as well if it is
+/// a sudo invocation, and in `commands` if that invocation has a command attached. Shared between the
+/// initial load and tailed reads so both stay in sync.
+fn process_line(line: &str, logs: &mut Vec<LogEntry>, sudo_logs: &mut Vec<LogEntry>, commands: &mut HashMap<String, usize>) {
+    let entry = parser::parse(line);
+
+    if let Some(sudo) = &entry.sudo {
+        if let Some(command) = &sudo.command {
+            *commands.entry(command.clone()).or_insert(0) += 1;
+        }
+        sudo_logs.push(entry.clone());
+    }
+
+    // add the entries to the logs vector
+    logs.push(entry);
+}
+
+fn load_logs(log_path: &str, commands: &mut HashMap<String, usize>) -> Result<(Vec<LogEntry>, Vec<LogEntry>, u64), Box<dyn Error>> {
     // open the auth.log file
-    let file = File::open("/var/log/auth.log")?;
+    let file = File::open(log_path)?;
+    let offset = file.metadata()?.len();
     let reader = BufReader::new(file);
 
     // vector to store the log entries
@@ -117,40 +342,51 @@ fn load_logs(commands: &mut HashMap<String, usize>) -> Result<(Vec<String>, Vec<
     for line in reader.lines() {
         // unwrap the line or handle any potential error
         let line = line?;
+        process_line(&line, &mut logs, &mut sudo_logs, commands);
+    }
+
+    Ok((logs, sudo_logs, offset))
+}
+
+/// Reads the members of the `sudo` group from `/etc/group`.
+pub fn get_sudoers() -> Result<Vec<String>, Box<dyn Error>> {
+    let file = File::open("/etc/group")?;
+    let reader = BufReader::new(file);
 
-        // if the log is sudo-related, parse the line and store the command used
-        if line.contains("sudo:") && !line.contains("pam_unix") {
-            let command_text = "COMMAND=";
-            let command_index = line.find(command_text).unwrap();
-            let (_, command )= line.split_at(command_index + command_text.len());
-            if commands.contains_key(command) {
-                commands.insert(command.to_string(), commands.get(command).unwrap() + 1);
-            } else {
-                commands.insert(command.to_string(), 1);
+    let mut sudoers = Vec::new();
+    for line in reader.lines() {
+        let line = line?;
+        let parsed_line: Vec<&str> = line.split_terminator(":").collect();
+        if parsed_line.first() == Some(&"sudo") {
+            let group_info_len = 3;
+            if parsed_line.len() > group_info_len {
+                let usernames: Vec<&str> = parsed_line[3].split_terminator(",").collect();
+                for u in usernames {
+                    sudoers.push(u.to_string());
+                }
             }
-            sudo_logs.push(line.clone());
         }
-
-        // add the entries to the logs vector
-        logs.push(line);
     }
-
-    Ok((logs, sudo_logs))
+    Ok(sudoers)
 }
 
 fn update_log_information(app: &mut App, size: &Rect) {
-    app.logs_per_page = size.height.into();
+    app.logs_per_page = app.config.logs_per_page
+        .unwrap_or_else(|| size.height.saturating_sub(CHROME_HEIGHT).max(1) as usize);
 
-    app.num_logs = match app.tab_index {
-        0 => app.logs.len(),
-        1 => app.sudo_logs.len(),
-        2 => app.sudo_logs.len(),
+    // the MANAGE tab doesn't page through logs at all; reuse the ALL source harmlessly
+    let source: &Vec<LogEntry> = match app.tab_index {
+        0 | 3 => &app.logs,
+        1 | 2 => &app.sudo_logs,
         _ => unreachable!()
     };
 
-    if app.num_logs % app.logs_per_page == 0 {
-        app.num_pages = app.num_logs / app.logs_per_page;
-    } else {
-        app.num_pages = (app.num_logs / app.logs_per_page) + 1;
-    }
-}
\ No newline at end of file
+    let query = app.prompt.query();
+    app.filtered = source.iter()
+        .enumerate()
+        .filter(|(_, entry)| query.is_empty() || prompt::find_match(&entry.raw, query).is_some())
+        .map(|(i, _)| i)
+        .collect();
+
+    app.num_logs = app.filtered.len();
+}
@@ -0,0 +1,43 @@
+use std::io::{self, Write};
+use std::process::{Command, Stdio};
+
+/// Combined stdout/stderr from a privileged command, along with whether it exited
+/// successfully, so the caller can surface failures (wrong password, insufficient
+/// privilege, ...) instead of panicking.
+pub struct CommandOutput {
+    pub success: bool,
+    pub output: String,
+}
+
+/// Runs `sudo <cmd> <args>`, writing `password` to the child's stdin so it's never
+/// stored anywhere else, and captures its combined stdout/stderr.
+fn run_privileged(cmd: &str, args: &[&str], password: &str) -> io::Result<CommandOutput> {
+    let mut child = Command::new("sudo")
+        .arg("-S")
+        .arg(cmd)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    if let Some(stdin) = child.stdin.as_mut() {
+        writeln!(stdin, "{}", password)?;
+    }
+
+    let result = child.wait_with_output()?;
+    let mut output = String::from_utf8_lossy(&result.stdout).into_owned();
+    output.push_str(&String::from_utf8_lossy(&result.stderr));
+
+    Ok(CommandOutput { success: result.status.success(), output })
+}
+
+/// Adds `user` to the `sudo` group via `usermod -aG sudo <user>`.
+pub fn add_to_sudo(user: &str, password: &str) -> io::Result<CommandOutput> {
+    run_privileged("usermod", &["-aG", "sudo", user], password)
+}
+
+/// Removes `user` from the `sudo` group via `gpasswd -d <user> sudo`.
+pub fn remove_from_sudo(user: &str, password: &str) -> io::Result<CommandOutput> {
+    run_privileged("gpasswd", &["-d", user, "sudo"], password)
+}
@@ -0,0 +1,132 @@
+use serde::Deserialize;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use tui::style::Color;
+
+/// Where the log file lives and how the UI should be colored, loaded once at
+/// startup. Falls back to the built-in defaults for any field that's missing
+/// from the config file, or if the file doesn't exist at all.
+pub struct Config {
+    pub log_path: String,
+    /// Overrides the number of log rows shown per screen; `None` means derive
+    /// it from the terminal height as usual.
+    pub logs_per_page: Option<usize>,
+    pub theme: Theme,
+}
+
+/// The colors used throughout the UI: chrome (title bar, info bar, tab
+/// highlight), accent (section titles, the frequency chart), text (the
+/// default, dimmed color for log lines and list entries), sudo_highlight
+/// (the "sudo" keyword in a sudo log line), username_highlight (the
+/// invoking user in that same line), highlight_text (text drawn on top of
+/// a highlight: the info bar and the selected row in MANAGE, both on
+/// chrome, and matched search text, on search_match), and search_match
+/// (the background of a highlighted search match).
+pub struct Theme {
+    pub chrome: Color,
+    pub accent: Color,
+    pub text: Color,
+    pub sudo_highlight: Color,
+    pub username_highlight: Color,
+    pub highlight_text: Color,
+    pub search_match: Color,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            log_path: "/var/log/auth.log".to_string(),
+            logs_per_page: None,
+            theme: Theme::default(),
+        }
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme {
+            chrome: Color::Rgb(200, 200, 200),
+            accent: Color::Rgb(217, 111, 13),
+            text: Color::Rgb(120, 120, 120),
+            sudo_highlight: Color::LightRed,
+            username_highlight: Color::White,
+            highlight_text: Color::Black,
+            search_match: Color::Yellow,
+        }
+    }
+}
+
+/// The on-disk shape of `config.toml`. Every field is optional so a file that
+/// only sets `log_path`, say, still leaves the rest at its built-in default.
+#[derive(Deserialize)]
+struct RawConfig {
+    log_path: Option<String>,
+    logs_per_page: Option<usize>,
+    theme: Option<RawTheme>,
+}
+
+#[derive(Deserialize)]
+struct RawTheme {
+    chrome: Option<[u8; 3]>,
+    accent: Option<[u8; 3]>,
+    text: Option<[u8; 3]>,
+    sudo_highlight: Option<[u8; 3]>,
+    username_highlight: Option<[u8; 3]>,
+    highlight_text: Option<[u8; 3]>,
+    search_match: Option<[u8; 3]>,
+}
+
+impl Config {
+    /// Loads `~/.config/tuisulog/config.toml`. Any problem reading or parsing
+    /// the file (it doesn't exist, `$HOME` isn't set, the TOML is malformed)
+    /// is treated the same way: fall back to `Config::default()` rather than
+    /// failing startup.
+    pub fn load() -> Config {
+        let defaults = Config::default();
+
+        let raw: RawConfig = match config_path().and_then(|path| fs::read_to_string(path).ok()) {
+            Some(contents) => match toml::from_str(&contents) {
+                Ok(raw) => raw,
+                Err(_) => return defaults,
+            },
+            None => return defaults,
+        };
+
+        let raw_theme = raw.theme.unwrap_or(RawTheme {
+            chrome: None,
+            accent: None,
+            text: None,
+            sudo_highlight: None,
+            username_highlight: None,
+            highlight_text: None,
+            search_match: None,
+        });
+
+        Config {
+            log_path: raw.log_path.unwrap_or(defaults.log_path),
+            logs_per_page: raw.logs_per_page,
+            theme: Theme {
+                chrome: raw_theme.chrome.map(rgb).unwrap_or(defaults.theme.chrome),
+                accent: raw_theme.accent.map(rgb).unwrap_or(defaults.theme.accent),
+                text: raw_theme.text.map(rgb).unwrap_or(defaults.theme.text),
+                sudo_highlight: raw_theme.sudo_highlight.map(rgb).unwrap_or(defaults.theme.sudo_highlight),
+                username_highlight: raw_theme.username_highlight.map(rgb).unwrap_or(defaults.theme.username_highlight),
+                highlight_text: raw_theme.highlight_text.map(rgb).unwrap_or(defaults.theme.highlight_text),
+                search_match: raw_theme.search_match.map(rgb).unwrap_or(defaults.theme.search_match),
+            },
+        }
+    }
+}
+
+fn rgb([r, g, b]: [u8; 3]) -> Color {
+    Color::Rgb(r, g, b)
+}
+
+fn config_path() -> Option<PathBuf> {
+    let mut path = PathBuf::from(env::var("HOME").ok()?);
+    path.push(".config");
+    path.push("tuisulog");
+    path.push("config.toml");
+    Some(path)
+}
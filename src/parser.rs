@@ -0,0 +1,132 @@
+/// The decomposed fields of a sudo invocation line, e.g.
+/// `ehelwig : TTY=pts/0 ; PWD=/home/ehelwig ; USER=root ; COMMAND=/usr/bin/apt update`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SudoInvocation {
+    pub user: String,
+    pub tty: Option<String>,
+    pub pwd: Option<String>,
+    pub target_user: Option<String>,
+    pub command: Option<String>,
+}
+
+/// A single syslog line from auth.log, decomposed into its standard fields.
+/// `sudo` is populated only when `process` is a sudo entry with the usual
+/// `user : TTY=... ; PWD=... ; USER=... ; COMMAND=...` body.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogEntry {
+    pub timestamp: String,
+    pub hostname: String,
+    pub process: String,
+    pub pid: Option<u32>,
+    pub message: String,
+    pub sudo: Option<SudoInvocation>,
+    pub raw: String,
+}
+
+impl LogEntry {
+    pub fn is_sudo(&self) -> bool {
+        self.sudo.is_some()
+    }
+
+    /// Wraps a line that doesn't match the standard syslog shape so it can still be
+    /// stored and rendered, just without any decomposed fields.
+    fn plain(line: &str) -> LogEntry {
+        LogEntry {
+            timestamp: String::new(),
+            hostname: String::new(),
+            process: String::new(),
+            pid: None,
+            message: line.to_string(),
+            sudo: None,
+            raw: line.to_string(),
+        }
+    }
+}
+
+/// Parses one line of auth.log into a `LogEntry`, falling back to a plain-text
+/// entry (no decomposed fields) for lines that don't match the standard syslog
+/// shape instead of panicking.
+pub fn parse(line: &str) -> LogEntry {
+    parse_line(line).unwrap_or_else(|| LogEntry::plain(line))
+}
+
+/// Parses one line of auth.log into a `LogEntry`. Returns `None` if the line
+/// doesn't match the standard `<timestamp> <hostname> <process>[pid]: <message>`
+/// syslog shape, in which case the caller should fall back to treating it as
+/// plain text rather than panicking.
+fn parse_line(line: &str) -> Option<LogEntry> {
+    // timestamp is the first three whitespace-separated tokens, e.g. "Jul 26 10:00:01"
+    // (syslog pads single-digit days with a second space, e.g. "Jul  6 10:00:01")
+    let (month, rest) = line.split_once(' ')?;
+    let (day, rest) = rest.trim_start().split_once(' ')?;
+    let (time, rest) = rest.split_once(' ')?;
+    let timestamp = format!("{} {} {}", month, day, time);
+
+    let mut rest_parts = rest.splitn(2, ' ');
+    let hostname = rest_parts.next()?.to_string();
+    let tag_and_message = rest_parts.next()?;
+
+    let (tag, message) = tag_and_message.split_once(": ")?;
+    let (process, pid) = match tag.split_once('[') {
+        Some((process, pid_and_bracket)) => {
+            let pid = pid_and_bracket.trim_end_matches(']').parse::<u32>().ok();
+            (process.to_string(), pid)
+        }
+        None => (tag.to_string(), None),
+    };
+
+    let sudo = if process == "sudo" {
+        parse_sudo_invocation(message)
+    } else {
+        None
+    };
+
+    Some(LogEntry {
+        timestamp,
+        hostname,
+        process,
+        pid,
+        message: message.to_string(),
+        sudo,
+        raw: line.to_string(),
+    })
+}
+
+/// Parses the body of a sudo log line, e.g.
+/// `ehelwig : TTY=pts/0 ; PWD=/home/ehelwig ; USER=root ; COMMAND=/usr/bin/apt update`.
+/// Returns `None` for sudo-tagged lines that don't have this shape (auth failures,
+/// pam_unix session lines, etc.) so they fall through and render as plain text.
+fn parse_sudo_invocation(message: &str) -> Option<SudoInvocation> {
+    let (user, fields) = message.split_once(" : ")?;
+
+    // `COMMAND=` is always the last field and runs to the end of the line, so
+    // it may itself contain " ; " (a shell command with an embedded semicolon,
+    // e.g. `sh -c "systemctl restart nginx ; systemctl status nginx"`).
+    // Only the fields before it are actually " ; "-delimited.
+    let (prefix, command) = match fields.find("COMMAND=") {
+        Some(index) => (&fields[..index], Some(fields[index + "COMMAND=".len()..].to_string())),
+        None => (fields, None),
+    };
+
+    let mut tty = None;
+    let mut pwd = None;
+    let mut target_user = None;
+
+    for field in prefix.split(" ; ") {
+        if let Some(value) = field.strip_prefix("TTY=") {
+            tty = Some(value.to_string());
+        } else if let Some(value) = field.strip_prefix("PWD=") {
+            pwd = Some(value.to_string());
+        } else if let Some(value) = field.strip_prefix("USER=") {
+            target_user = Some(value.to_string());
+        }
+    }
+
+    Some(SudoInvocation {
+        user: user.trim().to_string(),
+        tty,
+        pwd,
+        target_user,
+        command,
+    })
+}
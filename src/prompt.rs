@@ -0,0 +1,107 @@
+use crossterm::event::KeyCode;
+use regex::Regex;
+
+/// What the prompt did with a keypress: keep editing, or close either by
+/// committing the current buffer (Enter) or discarding it (Esc).
+pub enum PromptOutcome {
+    Continue,
+    Submit,
+    Cancel,
+}
+
+/// A one-line text input opened with '/' to filter the visible log list.
+/// Owns its own buffer and cursor position, independent of `App`.
+pub struct Prompt {
+    buffer: String,
+    cursor: usize,
+}
+
+impl Prompt {
+    pub fn new() -> Prompt {
+        Prompt { buffer: String::new(), cursor: 0 }
+    }
+
+    pub fn query(&self) -> &str {
+        &self.buffer
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+        self.cursor = 0;
+    }
+
+    /// Feeds one key to the prompt while it's focused.
+    pub fn handle_key(&mut self, code: KeyCode) -> PromptOutcome {
+        match code {
+            KeyCode::Char(c) => {
+                self.buffer.insert(self.cursor, c);
+                self.cursor += c.len_utf8();
+                PromptOutcome::Continue
+            }
+            KeyCode::Backspace => {
+                if self.cursor > 0 {
+                    let prev = self.prev_char_boundary();
+                    self.buffer.remove(prev);
+                    self.cursor = prev;
+                }
+                PromptOutcome::Continue
+            }
+            KeyCode::Left => {
+                self.cursor = self.prev_char_boundary();
+                PromptOutcome::Continue
+            }
+            KeyCode::Right => {
+                self.cursor = self.next_char_boundary();
+                PromptOutcome::Continue
+            }
+            KeyCode::Enter => PromptOutcome::Submit,
+            KeyCode::Esc => PromptOutcome::Cancel,
+            _ => PromptOutcome::Continue,
+        }
+    }
+
+    /// The byte index of the char boundary just before `self.cursor`, or 0 if
+    /// the cursor is already at the start. `cursor` is a byte offset (as
+    /// `String::insert`/`remove` require), so stepping by one byte at a time
+    /// would land inside a multi-byte UTF-8 character and panic.
+    fn prev_char_boundary(&self) -> usize {
+        let mut idx = self.cursor.saturating_sub(1);
+        while idx > 0 && !self.buffer.is_char_boundary(idx) {
+            idx -= 1;
+        }
+        idx
+    }
+
+    /// The byte index of the char boundary just after `self.cursor`, or the
+    /// buffer's length if the cursor is already at the end.
+    fn next_char_boundary(&self) -> usize {
+        let mut idx = (self.cursor + 1).min(self.buffer.len());
+        while idx < self.buffer.len() && !self.buffer.is_char_boundary(idx) {
+            idx += 1;
+        }
+        idx
+    }
+}
+
+/// Locates the first match of `query` within `text`. A query wrapped in slashes
+/// (e.g. `/COMMAND=.*apt/`) is treated as a regex; otherwise it's a plain substring
+/// search. Returns `None` for an empty query so callers can treat that as "no filter".
+pub fn find_match(text: &str, query: &str) -> Option<(usize, usize)> {
+    if query.len() >= 2 && query.starts_with('/') && query.ends_with('/') {
+        let pattern = &query[1..query.len() - 1];
+        let re = Regex::new(pattern).ok()?;
+        let m = re.find(text)?;
+        return Some((m.start(), m.end()));
+    }
+
+    if query.is_empty() {
+        return None;
+    }
+
+    let start = text.find(query)?;
+    Some((start, start + query.len()))
+}
@@ -1,17 +1,25 @@
-use std::{
-    io::{BufRead, BufReader},
-    fs::File,
-    error::Error
-};
 use tui::{
     backend::Backend,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Color, Style, Modifier},
+    style::{Style, Modifier},
     text::{Span, Spans},
     widgets::{Block, Wrap, Borders, Paragraph, Tabs},
     Frame,
 };
-use crate::app::App;
+use crate::app::{App, ManageAction, ManageStep};
+use crate::config::Theme;
+use crate::parser::{LogEntry, SudoInvocation};
+use crate::prompt;
+
+/// Vertical space taken up by chrome around the log rows: the 1-row margin on each
+/// side, the 3-row tabs block, the 1-row info bar, and the log pane's own top and
+/// bottom border. `App` uses this to size its scroll window to what's actually
+/// visible, not the raw terminal height.
+pub const CHROME_HEIGHT: u16 = 8;
+/// Smallest terminal size we'll try to render a real UI into; below this we just
+/// show a "too small" message instead of computing (and likely panicking on) slices.
+const MIN_HEIGHT: u16 = CHROME_HEIGHT + 1;
+const MIN_WIDTH: u16 = 20;
 
 /// Renders the user interface for the Super User Management Interface.
 ///
@@ -24,6 +32,11 @@ use crate::app::App;
 /// * `app`: A reference to an `App` struct, containing the data for the user interface.
 /// * `size`: A reference to a `Rect`, representing the available size to draw the UI.
 pub fn draw_ui<B: Backend>(f: &mut Frame<B>, app: &App, size: &Rect) {
+    if size.height < MIN_HEIGHT || size.width < MIN_WIDTH {
+        render_too_small(f, size, &app.config.theme);
+        return;
+    }
+
     let layout = Layout::default()
         .direction(Direction::Vertical)
         .margin(1)
@@ -34,11 +47,11 @@ pub fn draw_ui<B: Backend>(f: &mut Frame<B>, app: &App, size: &Rect) {
     let title_block = Block::default()
         .title("Super User Management Interface")
         .title_alignment(Alignment::Center)
-        .style(Style::default().fg(Color::Rgb(200,200,200)));
+        .style(Style::default().fg(app.config.theme.chrome));
     f.render_widget(title_block, *size);
 
     // render tabs
-    let tabs = create_tabs(&app.titles, app.tab_index);
+    let tabs = create_tabs(&app.titles, app.tab_index, &app.config.theme);
     f.render_widget(tabs, layout[0]);
 
     match app.tab_index {
@@ -62,46 +75,65 @@ pub fn draw_ui<B: Backend>(f: &mut Frame<B>, app: &App, size: &Rect) {
             f.render_widget(recent_cmds_paragraph, horiz_layout[0]);
 
             // render most used commands chart
-            let most_used_cmds_paragraph = create_most_used_cmds_paragraph(app);
-            f.render_widget(most_used_cmds_paragraph, horiz_layout[1]);
+            let most_used_cmds_chart = create_most_used_cmds_chart(app, horiz_layout[1].width);
+            f.render_widget(most_used_cmds_chart, horiz_layout[1]);
+        },
+        3 => {
+            // render sudoers list
+            let manage_paragraph = create_manage_paragraph(app);
+            f.render_widget(manage_paragraph, layout[1]);
+
+            // render manage prompt/status
+            let info_paragraph = create_info_paragraph(app);
+            f.render_widget(info_paragraph, layout[2]);
         },
         _ => unreachable!()
     }
 }
 
+/// Renders a centered message in place of the full UI when the terminal is too
+/// small to fit the title, tabs, and at least one log row.
+fn render_too_small<B: Backend>(f: &mut Frame<B>, size: &Rect, theme: &Theme) {
+    let message = Paragraph::new("terminal too small")
+        .alignment(Alignment::Center)
+        .style(Style::default().fg(theme.chrome));
+    f.render_widget(message, *size);
+}
+
 /// Creates and returns `Tabs` from a Vector of Strings. `Tabs` is a special
 /// type of block for displaying Spans in a multi-panel context.
 /// 
 /// # Arguments
 ///
 /// * `titles`: A reference to a `Vec<String>` object representing the tab titles.
-fn create_tabs(titles: &Vec<String>, tab_index: usize) -> Tabs {
+fn create_tabs(titles: &Vec<String>, tab_index: usize, theme: &Theme) -> Tabs<'static> {
     let titles = titles.iter().map(|t| {
         Spans::from(vec![
-            Span::styled(t.to_string(), Style::default().fg(Color::Rgb(120,120,120)))
+            Span::styled(t.to_string(), Style::default().fg(theme.text))
         ])
     }).collect();
 
     Tabs::new(titles)
         .block(Block::default().borders(Borders::ALL).title(Span::styled(
             "Tabs",
-            Style::default().fg(Color::Rgb(217,111,13))
+            Style::default().fg(theme.accent)
         )))
         .select(tab_index)
-        .style(Style::default().fg(Color::Rgb(120,120,120)))
+        .style(Style::default().fg(theme.text))
         .highlight_style(Style::default()
-            .fg(Color::Rgb(200,200,200))
+            .fg(theme.chrome)
             .add_modifier(Modifier::BOLD)
     )
 }
 
 fn create_logs_paragraph(app: &App) -> Paragraph {
+    let theme = &app.config.theme;
     let log_block = Block::default()
         .borders(Borders::ALL)
-        .title(Span::styled("Logs", Style::default().fg(Color::Rgb(217,111,13))))
-        .style(Style::default().fg(Color::Rgb(120,120,120)));
+        .title(Span::styled("Logs", Style::default().fg(theme.accent)))
+        .style(Style::default().fg(theme.text));
     let page = get_page(app);
-    let spans = create_spans(page);
+    let spans = create_spans(&page, app.prompt.query(), theme);
 
     Paragraph::new(spans)
         .block(log_block)
@@ -111,9 +143,19 @@ fn create_logs_paragraph(app: &App) -> Paragraph {
 
 fn create_info_paragraph(app: &App) -> Paragraph {
     let info_block = Block::default();
-    let info_text = String::from(format!("\"/var/log/auth.log\" page: {}/{} logs: {}  (use arrow keys to navigate, press q to exit) ", app.page_index + 1, app.num_pages, app.num_logs));
+    let info_text = if app.searching {
+        format!("/{}", app.prompt.query())
+    } else if app.tab_index == 3 {
+        manage_info_text(app)
+    } else {
+        let follow_text = if app.follow { "on" } else { "off" };
+        let first = app.scroll.min(app.num_logs);
+        let last = (first + app.logs_per_page).min(app.num_logs);
+        let first_display = if app.num_logs == 0 { 0 } else { first + 1 };
+        format!("\"{}\" lines: {}-{}/{} follow: {}  (arrow keys/PgUp/PgDn/Home/End to scroll, / to search, f to toggle follow, q to exit) ", app.config.log_path, first_display, last, app.num_logs, follow_text)
+    };
     let text_spans = Spans::from(
-        Span::styled(info_text, Style::default().bg(Color::Rgb(200,200,200)).fg(Color::Black))
+        Span::styled(info_text, Style::default().bg(app.config.theme.chrome).fg(app.config.theme.highlight_text))
     );
 
     Paragraph::new(text_spans)
@@ -121,30 +163,64 @@ fn create_info_paragraph(app: &App) -> Paragraph {
         .alignment(Alignment::Left)
 }
 
+/// Describes the MANAGE tab's current state machine step for the info bar.
+fn manage_info_text(app: &App) -> String {
+    match &app.manage_step {
+        ManageStep::Idle => "a: add user  enter: remove selected  use arrow keys to navigate  q: quit".to_string(),
+        ManageStep::EnteringUser => format!("add user: {}", app.manage_input.query()),
+        ManageStep::ConfirmAction { action, user } => {
+            let verb = match action { ManageAction::Add => "add", ManageAction::Remove => "remove" };
+            let prep = match action { ManageAction::Add => "to", ManageAction::Remove => "from" };
+            format!("{} \"{}\" {} sudo? (y/n)", verb, user, prep)
+        }
+        ManageStep::EnteringPassword { user, .. } => format!("sudo password for {}: {}", user, "*".repeat(app.manage_input.query().len())),
+        ManageStep::Result(message) => format!("{} (press any key)", message),
+    }
+}
+
+fn create_manage_paragraph(app: &App) -> Paragraph {
+    let theme = &app.config.theme;
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(Span::styled("Sudoers", Style::default().fg(theme.accent)))
+        .style(Style::default().fg(theme.text));
+
+    let mut spans: Vec<Spans> = Vec::new();
+    for (index, user) in app.sudoers.iter().enumerate() {
+        let style = if index == app.manage_selected {
+            Style::default().fg(theme.highlight_text).bg(theme.chrome)
+        } else {
+            Style::default().fg(theme.text)
+        };
+        spans.push(Spans::from(Span::styled(user.to_string(), style)));
+    }
+
+    Paragraph::new(spans)
+        .block(block)
+        .wrap(Wrap{trim: true})
+        .alignment(Alignment::Left)
+}
+
 fn create_recent_commands_paragraph(app: &App) -> Paragraph {
+    let theme = &app.config.theme;
     let block = Block::default()
         .borders(Borders::ALL)
-        .title(Span::styled("Recent", Style::default().fg(Color::Rgb(217,111,13))))
-        .style(Style::default().fg(Color::Rgb(120,120,120)));
+        .title(Span::styled("Recent", Style::default().fg(theme.accent)))
+        .style(Style::default().fg(theme.text));
 
     let mut spans: Vec<Spans> = Vec::new();
-    let cmd_text = "COMMAND=";
     let recent_logs_to_get = 10;
-    let recent_log_index = app.num_logs - recent_logs_to_get;
-    for log in &app.sudo_logs[recent_log_index..] {
+    let recent_log_index = app.sudo_logs.len().saturating_sub(recent_logs_to_get);
+    for entry in &app.sudo_logs[recent_log_index..] {
+        let sudo = entry.sudo.as_ref().unwrap();
         let mut span: Vec<Span> = Vec::new();
         // user
-        let sudo_text = "sudo:";
-        let delimiter = ":";
-        let user_start_index = log.find(sudo_text).unwrap() + sudo_text.len();
-        let user_end_index = log[user_start_index..].find(delimiter).unwrap() + user_start_index;
-        let user = format!("{}: ", log[user_start_index..user_end_index].trim());
-        span.push(Span::styled(user, Style::default().fg(Color::Rgb(120,120,120))));
+        let user = format!("{}: ", sudo.user);
+        span.push(Span::styled(user, Style::default().fg(theme.text)));
 
         // command
-        let cmd_index = log.find(cmd_text).unwrap();
-        let (_, cmd) = log.split_at(cmd_index + cmd_text.len());
-        span.push(Span::styled(cmd, Style::default().fg(Color::Rgb(120,120,120))));
+        let cmd = sudo.command.clone().unwrap_or_default();
+        span.push(Span::styled(cmd, Style::default().fg(theme.text)));
 
         spans.push(Spans::from(span));
     }
@@ -155,97 +231,75 @@ fn create_recent_commands_paragraph(app: &App) -> Paragraph {
         .alignment(Alignment::Left)
 }
 
-fn create_most_used_cmds_paragraph(app: &App) -> Paragraph {
+/// Builds a horizontal bar chart of sudo command frequencies, most-used first: each
+/// row is an elided command label, a bar whose width is proportional to
+/// `count / max_count` across the pane, and the count itself. Hand-rolled rather
+/// than `tui`'s stock `BarChart` (which only draws vertical bars and truncates
+/// labels to the bar width), since these command strings are long.
+fn create_most_used_cmds_chart(app: &App, width: u16) -> Paragraph {
+    let theme = &app.config.theme;
     let block = Block::default()
         .borders(Borders::ALL)
-        .title(Span::styled("Frequency", Style::default().fg(Color::Rgb(217,111,13))))
-        .style(Style::default().fg(Color::Rgb(120,120,120)));
-
-    let mut spans: Vec<Spans> = Vec::new();
+        .title(Span::styled("Frequency", Style::default().fg(theme.accent)))
+        .style(Style::default().fg(theme.text));
 
     // Extract commands and sort their frequencies in descending order
     let mut sorted_pairs: Vec<(&String, &usize)> = app.commands.iter().collect();
-    sorted_pairs.sort_by(|a, b| b.1.cmp(a.1)); // Sorting in descending order
-
+    sorted_pairs.sort_by(|a, b| b.1.cmp(a.1));
+
+    let max_count = sorted_pairs.first().map(|(_, count)| **count).unwrap_or(0);
+    let inner_width = width.saturating_sub(2) as usize; // account for the block's borders
+    let count_width = sorted_pairs.iter()
+        .map(|(_, count)| format!(" ({})", count).chars().count())
+        .max()
+        .unwrap_or(0);
+    let label_width = inner_width / 3;
+    let bar_width = inner_width.saturating_sub(label_width + count_width).max(1);
+
+    let mut rows: Vec<Spans> = Vec::new();
     for (command, count) in sorted_pairs {
-        let mut span: Vec<Span> = Vec::new();
-        let count_text = format!(" ({})", count);
-        span.push(Span::styled(command, Style::default().fg(Color::Rgb(120,120,120))));
-        span.push(Span::styled(count_text, Style::default().fg(Color::Rgb(120,120,120))));
-        spans.push(Spans::from(span));
+        let label = elide(command, label_width);
+        let filled = if max_count == 0 { 0 } else { count * bar_width / max_count };
+        let filled = if *count > 0 { filled.max(1) } else { 0 };
+
+        rows.push(Spans::from(vec![
+            Span::styled(format!("{:<width$}", label, width = label_width), Style::default().fg(theme.text)),
+            Span::styled("█".repeat(filled), Style::default().fg(theme.accent)),
+            Span::styled(format!(" ({})", count), Style::default().fg(theme.text)),
+        ]));
     }
 
-    Paragraph::new(spans)
+    Paragraph::new(rows)
         .block(block)
         .wrap(Wrap{trim: true})
         .alignment(Alignment::Left)
 }
 
-fn create_spans(page: &[String]) -> Vec<Spans> {
-    let sudoers = get_sudoers().unwrap();
+/// Truncates `text` to `max_width` characters, replacing the last one with an
+/// ellipsis if it didn't already fit, so long command strings still leave room
+/// for the bar and count next to them.
+fn elide(text: &str, max_width: usize) -> String {
+    if text.chars().count() <= max_width {
+        return text.to_string();
+    }
+    if max_width == 0 {
+        return String::new();
+    }
+
+    let mut truncated: String = text.chars().take(max_width.saturating_sub(1)).collect();
+    truncated.push('…');
+    truncated
+}
+
+fn create_spans(page: &[&LogEntry], query: &str, theme: &Theme) -> Vec<Spans<'static>> {
     let mut text_spans: Vec<Spans> = Vec::new();
 
-    for log in page {
-        let mut spans = Vec::new();
-        let sudo_text = "sudo";
-        // parse through lines to color code "sudo" and super users for readability
-        if log.contains(sudo_text) {
-            let mut log_index = 0;
-            let mut is_match = false;
-            let mut word = String::new();
-            for ch in log.chars() {
-                word.push(ch);
-                let mut could_match = false;
-
-                if sudo_text == word {
-                    is_match = true;
-                    // seperates the line into sections
-                    let rest = &log[log_index..];
-                    let index = rest.find(sudo_text).unwrap();
-                    let (front, _) = rest.split_at(index);
-                    log_index += front.len() + sudo_text.len();
-                    // add sections that are ready
-                    spans.push(Span::styled(front.to_string(), Style::default().fg(Color::Rgb(120,120,120))));
-                    spans.push(Span::styled(sudo_text.to_string(), Style::default().fg(Color::LightRed)));
-                } 
-                else if sudo_text.starts_with(&word) {
-                    could_match = true;
-                }
-                else {
-                    for username in &sudoers {
-                        // if the word built this far matches a username
-                        if *username == word {
-                            is_match = true;
-                            let rest = &log[log_index..];
-                            // parse it and push the completed sections
-                            let index = rest.find(username).unwrap();
-                            let (front, _) = rest.split_at(index);
-                            log_index += front.len() + username.len();
-
-                            if !front.is_empty() {
-                                spans.push(Span::styled(front.to_string(), Style::default().fg(Color::Rgb(120,120,120))));
-                            }
-                            spans.push(Span::styled(username.to_string(), Style::default().fg(Color::White)));
-                        } // but if it still matches the start of a username
-                        else if username.starts_with(&word) {
-                            could_match = true;
-                        }
-                    }
-                }
-
-                // if nothing matches or a username was found, clear the word
-                if !could_match || is_match {
-                    word.clear();
-                    is_match = false;
-                }
-            }
-
-            if !log[log_index..].is_empty() {
-                spans.push(Span::styled(log[log_index..].to_string(), Style::default().fg(Color::Rgb(120,120,120))));
-            }
-        } else {
-            spans.push(Span::styled(log.to_string(), Style::default().fg(Color::Rgb(120,120,120))));
-        }
+    for entry in page {
+        let spans = match &entry.sudo {
+            // color code "sudo" and the invoking user for readability
+            Some(sudo) => sudo_spans(&entry.raw, sudo, query, theme),
+            None => default_spans(&entry.raw, query, theme),
+        };
 
         text_spans.push(Spans::from(spans));
     }
@@ -253,39 +307,60 @@ fn create_spans(page: &[String]) -> Vec<Spans> {
     text_spans
 }
 
-fn get_sudoers() -> Result<Vec<String>, Box<dyn Error>> {
-    let file = File::open("/etc/group")?;
-    let reader = BufReader::new(file);
-
-    let mut sudoers = Vec::new();
-    for line in reader.lines() {
-        let line = line?;
-        if line.contains("sudo") {
-            let parsed_line: Vec<&str> = line.split_terminator(":").collect();
-            let group_info_len = 3;
-            if parsed_line.len() > group_info_len {
-                let usernames: Vec<&str> = parsed_line[3].split_terminator(",").collect();
-                for u in usernames {
-                    sudoers.push(u.to_string());
-                }
-            }
+/// Builds the highlighted spans for a sudo log line using its already-parsed fields,
+/// rather than re-scanning the raw text character by character.
+fn sudo_spans(raw: &str, sudo: &SudoInvocation, query: &str, theme: &Theme) -> Vec<Span<'static>> {
+    let sudo_text = "sudo";
+    let sudo_index = match raw.find(sudo_text) {
+        Some(index) => index,
+        None => return default_spans(raw, query, theme),
+    };
+
+    let (before_sudo, after_sudo) = raw.split_at(sudo_index);
+    let (_, rest) = after_sudo.split_at(sudo_text.len());
+
+    let mut spans = default_spans(before_sudo, query, theme);
+    spans.push(Span::styled(sudo_text.to_string(), Style::default().fg(theme.sudo_highlight)));
+
+    match rest.find(sudo.user.as_str()) {
+        Some(user_index) => {
+            let (before_user, after_user_start) = rest.split_at(user_index);
+            let (user, after_user) = after_user_start.split_at(sudo.user.len());
+            spans.extend(default_spans(before_user, query, theme));
+            spans.push(Span::styled(user.to_string(), Style::default().fg(theme.username_highlight)));
+            spans.extend(default_spans(after_user, query, theme));
         }
+        None => spans.extend(default_spans(rest, query, theme)),
     }
-    Ok(sudoers)
+
+    spans
 }
 
-fn get_page(app: &App) -> &[String] {
-    let first_log = app.page_index * app.logs_per_page;
-    let last_log: usize;
-    if app.page_index == app.num_pages - 1 {
-        last_log = app.num_logs - 1;
-    } else {
-        last_log = first_log + app.logs_per_page - 1;
+/// Renders a plain-text chunk, highlighting the first match of `query` within it if any.
+fn default_spans(text: &str, query: &str, theme: &Theme) -> Vec<Span<'static>> {
+    let default_style = Style::default().fg(theme.text);
+
+    match prompt::find_match(text, query) {
+        Some((start, end)) => vec![
+            Span::styled(text[..start].to_string(), default_style),
+            Span::styled(text[start..end].to_string(), default_style.bg(theme.search_match).fg(theme.highlight_text)),
+            Span::styled(text[end..].to_string(), default_style),
+        ],
+        None => vec![Span::styled(text.to_string(), default_style)],
     }
+}
 
-    match app.tab_index {
-        0 => &app.logs[first_log..last_log],
-        1 => &app.sudo_logs[first_log..last_log],
+/// Returns the entries visible in the scroll window `[scroll, scroll + logs_per_page)`,
+/// clamped to however many matches there actually are.
+fn get_page(app: &App) -> Vec<&LogEntry> {
+    let first = app.scroll.min(app.filtered.len());
+    let last = (first + app.logs_per_page).min(app.filtered.len());
+
+    let source: &Vec<LogEntry> = match app.tab_index {
+        0 => &app.logs,
+        1 => &app.sudo_logs,
         _ => unreachable!()
-    }
+    };
+
+    app.filtered[first..last].iter().map(|&i| &source[i]).collect()
 }
\ No newline at end of file